@@ -0,0 +1,209 @@
+//! Asynchronous, non-blocking access to the file-decompiling service
+//! ([decompiler](https://retdec.com/api/docs/decompiler.html)).
+
+use futures::Future;
+
+use auth::ApiAuth;
+use async_connection::AsyncAPIConnection;
+use async_connection::AsyncAPIConnectionFactory;
+use async_connection::AsyncResult;
+use async_connection::ReqwestAPIConnectionFactory;
+use decompilation::DecompilationArguments;
+use settings::Settings;
+
+/// Asynchronous, non-blocking file-decompiling service.
+///
+/// Unlike [`Decompiler`](../decompiler/struct.Decompiler.html),
+/// `AsyncDecompiler` returns futures instead of blocking, which allows many
+/// decompilations to be driven concurrently on a single Tokio runtime.
+///
+/// # Examples
+///
+/// ```no_run
+/// # extern crate futures;
+/// # extern crate retdec;
+/// # use retdec::error::Result;
+/// # fn test() -> Result<()> {
+/// use futures::Future;
+///
+/// use retdec::async_decompiler::AsyncDecompiler;
+/// use retdec::decompilation::DecompilationArguments;
+/// use retdec::file::File;
+/// use retdec::settings::Settings;
+///
+/// let settings = Settings::new()
+///     .with_api_key("MY-API-KEY");
+/// let decompiler = AsyncDecompiler::new(settings);
+/// let args = DecompilationArguments::new()
+///     .with_input_file(File::from_path("file.exe")?);
+/// let decompilation = decompiler.start_decompilation(args).wait()?;
+/// # Ok(()) } fn main() { test().unwrap() }
+/// ```
+pub struct AsyncDecompiler {
+    conn_factory: Box<AsyncAPIConnectionFactory>,
+}
+
+impl AsyncDecompiler {
+    /// Creates a new instance of the asynchronous file-decompiling service,
+    /// authenticating with the API key from `settings` (if any) via HTTP
+    /// Basic authentication.
+    pub fn new(settings: Settings) -> Self {
+        AsyncDecompiler {
+            conn_factory: Box::new(ReqwestAPIConnectionFactory::new(settings)),
+        }
+    }
+
+    /// Creates a new instance of the asynchronous file-decompiling service,
+    /// authenticating requests with the given `auth` instead of the default
+    /// API-key-based HTTP Basic authentication.
+    pub fn with_auth(settings: Settings, auth: Box<ApiAuth>) -> Self {
+        AsyncDecompiler {
+            conn_factory: Box::new(ReqwestAPIConnectionFactory::with_auth(settings, auth)),
+        }
+    }
+
+    /// Starts a new decompilation with the given arguments.
+    pub fn start_decompilation(&self, args: DecompilationArguments) -> AsyncResult<AsyncDecompilation> {
+        let mut conn = self.conn_factory.new_connection();
+        let url = format!("{}/decompiler/decompilations", conn.api_url());
+        let api_args = match args.into_api_args() {
+            Ok(api_args) => api_args,
+            Err(err) => return Box::new(::futures::future::err(err.into())),
+        };
+        Box::new(
+            conn.send_post_request(&url, api_args)
+                .map(move |response| {
+                    let id = response
+                        .json_value_as_string("id")
+                        .unwrap_or_else(String::new);
+                    AsyncDecompilation::new(id, conn)
+                }),
+        )
+    }
+
+    #[cfg(test)]
+    fn with_conn_factory(conn_factory: Box<AsyncAPIConnectionFactory>) -> Self {
+        AsyncDecompiler {
+            conn_factory: conn_factory,
+        }
+    }
+}
+
+/// A running or finished decompilation, accessed asynchronously.
+///
+/// This is the asynchronous counterpart of
+/// [`Decompilation`](../decompilation/struct.Decompilation.html).
+pub struct AsyncDecompilation {
+    id: String,
+    conn: Box<AsyncAPIConnection>,
+}
+
+impl AsyncDecompilation {
+    fn new(id: String, conn: Box<AsyncAPIConnection>) -> Self {
+        AsyncDecompilation { id: id, conn: conn }
+    }
+
+    /// Returns the ID of the decompilation.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Has the decompilation finished?
+    pub fn has_finished(&mut self) -> AsyncResult<bool> {
+        let url = format!(
+            "{}/decompiler/decompilations/{}/status",
+            self.conn.api_url(),
+            self.id
+        );
+        Box::new(
+            self.conn
+                .send_get_request(&url)
+                .map(|response| response.json_value_as_bool("finished").unwrap_or(false)),
+        )
+    }
+
+    /// Obtains and returns the decompiled code in the requested
+    /// high-level-language format.
+    pub fn get_output_hll_code(&mut self) -> AsyncResult<String> {
+        let url = format!(
+            "{}/decompiler/decompilations/{}/outputs/hll",
+            self.conn.api_url(),
+            self.id
+        );
+        Box::new(
+            self.conn
+                .send_get_request(&url)
+                .and_then(|response| response.body_as_string().map_err(|err| err.into())),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::Arc;
+    use std::sync::Mutex;
+
+    use async_connection::tests::AsyncAPIConnectionFactoryMock;
+    use async_connection::tests::AsyncAPIConnectionMock;
+    use async_connection::tests::APIArgumentsBuilder;
+    use async_connection::tests::APIResponseBuilder;
+    use decompilation::DecompilationArguments;
+    use file::File;
+
+    fn create_decompiler() -> (Arc<Mutex<AsyncAPIConnectionMock>>, AsyncDecompiler) {
+        // We need to force an API URL to prevent it from being overridden by
+        // setting the RETDEC_API_URL environment variable.
+        let settings = Settings::new()
+            .with_api_key("test")
+            .with_api_url("https://retdec.com/service/api");
+        let conn = Arc::new(Mutex::new(AsyncAPIConnectionMock::new(settings.clone())));
+        let conn_factory = Box::new(AsyncAPIConnectionFactoryMock::new(conn.clone()));
+        (conn, AsyncDecompiler::with_conn_factory(conn_factory))
+    }
+
+    #[test]
+    fn async_decompiler_start_decompilation_starts_decompilation_with_correct_arguments() {
+        let (conn, decompiler) = create_decompiler();
+        let input_file = File::from_content_with_name(b"content", "file.exe");
+        let args = DecompilationArguments::new()
+            .with_input_file(input_file.clone());
+        conn.lock().unwrap().add_response(
+            "POST",
+            "https://retdec.com/service/api/decompiler/decompilations",
+            Ok(
+                APIResponseBuilder::new()
+                    .with_status_code(200)
+                    .with_body(br#"{
+                        "id": "ID"
+                    }"#)
+                    .build()
+            )
+        );
+
+        let decompilation = decompiler.start_decompilation(args).wait()
+            .expect("decompilation should have succeeded");
+
+        assert_eq!(decompilation.id(), "ID");
+        assert!(conn.lock().unwrap().request_sent(
+            "POST",
+            "https://retdec.com/service/api/decompiler/decompilations",
+            APIArgumentsBuilder::new()
+                .with_string_arg("mode", "bin")
+                .with_file("input", input_file)
+                .build()
+        ));
+    }
+
+    #[test]
+    fn async_decompiler_start_decompilation_returns_error_when_input_file_is_not_given() {
+        let (_conn, decompiler) = create_decompiler();
+        let args = DecompilationArguments::new();
+
+        let result = decompiler.start_decompilation(args).wait();
+
+        let err = result.err().expect("expected start_decompilation() to fail");
+        assert_eq!(err.description(), "no input file given");
+    }
+}
@@ -0,0 +1,179 @@
+//! Asynchronous, non-blocking access to the file-analyzing service
+//! ([fileinfo](https://retdec.com/api/docs/fileinfo.html)).
+
+use futures::Future;
+
+use analysis::AnalysisArguments;
+use auth::ApiAuth;
+use async_connection::AsyncAPIConnection;
+use async_connection::AsyncAPIConnectionFactory;
+use async_connection::AsyncResult;
+use async_connection::ReqwestAPIConnectionFactory;
+use connection::APIArguments;
+use error::Result;
+use settings::Settings;
+
+/// Asynchronous, non-blocking file-analyzing service.
+///
+/// Unlike [`Fileinfo`](../fileinfo/struct.Fileinfo.html), `AsyncFileinfo`
+/// returns futures instead of blocking.
+pub struct AsyncFileinfo {
+    conn_factory: Box<AsyncAPIConnectionFactory>,
+}
+
+impl AsyncFileinfo {
+    /// Creates a new instance of the asynchronous file-analyzing service,
+    /// authenticating with the API key from `settings` (if any) via HTTP
+    /// Basic authentication.
+    pub fn new(settings: Settings) -> Self {
+        AsyncFileinfo {
+            conn_factory: Box::new(ReqwestAPIConnectionFactory::new(settings)),
+        }
+    }
+
+    /// Creates a new instance of the asynchronous file-analyzing service,
+    /// authenticating requests with the given `auth` instead of the default
+    /// API-key-based HTTP Basic authentication.
+    pub fn with_auth(settings: Settings, auth: Box<ApiAuth>) -> Self {
+        AsyncFileinfo {
+            conn_factory: Box::new(ReqwestAPIConnectionFactory::with_auth(settings, auth)),
+        }
+    }
+
+    /// Starts a new file analysis with the given arguments.
+    pub fn start_analysis(&self, args: &AnalysisArguments) -> AsyncResult<AsyncAnalysis> {
+        let mut conn = self.conn_factory.new_connection();
+        let url = format!("{}/fileinfo/analyses", conn.api_url());
+        let api_args = match self.create_api_args(args) {
+            Ok(api_args) => api_args,
+            Err(err) => return Box::new(::futures::future::err(err.into())),
+        };
+        Box::new(
+            conn.send_post_request(&url, api_args)
+                .map(move |response| {
+                    let id = response
+                        .json_value_as_string("id")
+                        .unwrap_or_else(String::new);
+                    AsyncAnalysis::new(id, conn)
+                }),
+        )
+    }
+
+    fn create_api_args(&self, args: &AnalysisArguments) -> Result<APIArguments> {
+        let mut api_args = APIArguments::new();
+        api_args.add_opt_string_arg("output_format", args.output_format());
+        api_args.add_opt_bool_arg("verbose", args.verbose());
+        match args.input_file() {
+            Some(ref input_file) => {
+                api_args.add_file("input", input_file);
+            }
+            None => {
+                bail!("no input file given");
+            }
+        }
+        Ok(api_args)
+    }
+
+    #[cfg(test)]
+    fn with_conn_factory(conn_factory: Box<AsyncAPIConnectionFactory>) -> Self {
+        AsyncFileinfo {
+            conn_factory: conn_factory,
+        }
+    }
+}
+
+/// A running or finished analysis, accessed asynchronously.
+pub struct AsyncAnalysis {
+    id: String,
+    conn: Box<AsyncAPIConnection>,
+}
+
+impl AsyncAnalysis {
+    fn new(id: String, conn: Box<AsyncAPIConnection>) -> Self {
+        AsyncAnalysis { id: id, conn: conn }
+    }
+
+    /// Returns the ID of the analysis.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Has the analysis finished?
+    pub fn has_finished(&mut self) -> AsyncResult<bool> {
+        let url = format!("{}/fileinfo/analyses/{}/status", self.conn.api_url(), self.id);
+        Box::new(
+            self.conn
+                .send_get_request(&url)
+                .map(|response| response.json_value_as_bool("finished").unwrap_or(false)),
+        )
+    }
+
+    /// Obtains and returns the output of the analysis.
+    pub fn get_output(&mut self) -> AsyncResult<String> {
+        let url = format!("{}/fileinfo/analyses/{}/output", self.conn.api_url(), self.id);
+        Box::new(
+            self.conn
+                .send_get_request(&url)
+                .and_then(|response| response.body_as_string().map_err(|err| err.into())),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::path::Path;
+    use std::sync::Arc;
+    use std::sync::Mutex;
+
+    use async_connection::tests::AsyncAPIConnectionFactoryMock;
+    use async_connection::tests::AsyncAPIConnectionMock;
+    use async_connection::tests::APIResponseBuilder;
+
+    fn create_fileinfo() -> (Arc<Mutex<AsyncAPIConnectionMock>>, AsyncFileinfo) {
+        // We need to force an API URL to prevent it from being overridden by
+        // setting the RETDEC_API_URL environment variable.
+        let settings = Settings::new()
+            .with_api_key("test")
+            .with_api_url("https://retdec.com/service/api");
+        let conn = Arc::new(Mutex::new(AsyncAPIConnectionMock::new(settings.clone())));
+        let conn_factory = Box::new(AsyncAPIConnectionFactoryMock::new(conn.clone()));
+        (conn, AsyncFileinfo::with_conn_factory(conn_factory))
+    }
+
+    #[test]
+    fn async_fileinfo_start_analysis_returns_analysis_with_returned_id() {
+        let (conn, fileinfo) = create_fileinfo();
+        let args = AnalysisArguments::new()
+            .with_input_file(Path::new("file.exe").to_path_buf());
+        conn.lock().unwrap().add_response(
+            "POST",
+            "https://retdec.com/service/api/fileinfo/analyses",
+            Ok(
+                APIResponseBuilder::new()
+                    .with_status_code(200)
+                    .with_body(br#"{
+                        "id": "ID"
+                    }"#)
+                    .build()
+            )
+        );
+
+        let analysis = fileinfo.start_analysis(&args).wait()
+            .expect("analysis should have succeeded");
+
+        assert_eq!(analysis.id(), "ID");
+    }
+
+    #[test]
+    fn async_fileinfo_start_analysis_returns_error_when_input_file_is_not_given() {
+        let (_conn, fileinfo) = create_fileinfo();
+        let args = AnalysisArguments::new();
+
+        let result = fileinfo.start_analysis(&args).wait();
+
+        let err = result.err().expect("expected start_analysis() to fail");
+        assert_eq!(err.description(), "no input file given");
+    }
+}
@@ -0,0 +1,583 @@
+//! Blocking connections to the retdec.com API, built on top of `hyper`.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::Hash;
+use std::hash::Hasher;
+use std::io::Read as IoRead;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+
+use hyper;
+use hyper::client::IntoUrl;
+use hyper::header::Authorization;
+use hyper::header::Basic;
+use hyper::header::Headers;
+use hyper::header::UserAgent;
+use hyper::net::HttpsConnector;
+use hyper::Url;
+use hyper_native_tls::NativeTlsClient;
+use serde_json;
+use serde_json::Value;
+
+use auth::ApiAuth;
+use auth::ApiKeyAuth;
+use error::Result;
+use error::ResultExt;
+use file::File;
+use settings::Settings;
+
+/// Arguments to be sent in a request to the retdec.com API.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct APIArguments {
+    pub(crate) string_args: Vec<(String, String)>,
+    pub(crate) file_args: Vec<(String, File)>,
+}
+
+impl APIArguments {
+    /// Creates an empty set of arguments.
+    pub fn new() -> Self {
+        APIArguments::default()
+    }
+
+    /// Adds a string argument.
+    pub fn add_string_arg<S: Into<String>>(&mut self, name: &str, value: S) {
+        self.string_args.push((name.to_string(), value.into()));
+    }
+
+    /// Adds a string argument when `value` is present.
+    pub fn add_opt_string_arg(&mut self, name: &str, value: &Option<String>) {
+        if let Some(ref value) = *value {
+            self.add_string_arg(name, value.clone());
+        }
+    }
+
+    /// Adds a boolean argument when `value` is present.
+    pub fn add_opt_bool_arg(&mut self, name: &str, value: Option<bool>) {
+        if let Some(value) = value {
+            self.add_string_arg(name, if value { "true" } else { "false" });
+        }
+    }
+
+    /// Adds a file to be uploaded.
+    pub fn add_file(&mut self, name: &str, file: File) {
+        self.file_args.push((name.to_string(), file));
+    }
+}
+
+/// A response from the retdec.com API.
+#[derive(Clone, Debug)]
+pub struct APIResponse {
+    status_code: u16,
+    body: Vec<u8>,
+}
+
+impl APIResponse {
+    /// Creates a new response from the given status code and body.
+    pub fn new(status_code: u16, body: Vec<u8>) -> Self {
+        APIResponse {
+            status_code: status_code,
+            body: body,
+        }
+    }
+
+    /// Returns the HTTP status code of the response.
+    pub fn status_code(&self) -> u16 {
+        self.status_code
+    }
+
+    /// Returns the body of the response as a string.
+    pub fn body_as_string(&self) -> Result<String> {
+        Ok(String::from_utf8(self.body.clone())?)
+    }
+
+    /// Parses and returns the body of the response as JSON.
+    pub fn body_as_json(&self) -> Result<Value> {
+        Ok(serde_json::from_slice(&self.body)?)
+    }
+
+    /// Returns the value of the given top-level JSON key as a string, if the
+    /// body is a JSON object containing it.
+    pub fn json_value_as_string(&self, key: &str) -> Option<String> {
+        self.body_as_json()
+            .ok()
+            .and_then(|json| json[key].as_str().map(String::from))
+    }
+
+    /// Returns the value of the given top-level JSON key as a boolean, if the
+    /// body is a JSON object containing it.
+    pub fn json_value_as_bool(&self, key: &str) -> Option<bool> {
+        self.body_as_json().ok().and_then(|json| json[key].as_bool())
+    }
+}
+
+/// A connection to the retdec.com API.
+pub trait APIConnection {
+    /// Returns the URL to the retdec.com API.
+    fn api_url(&self) -> String;
+
+    /// Sends a POST request with the given arguments to the given URL.
+    fn send_post_request(&mut self, url: &str, args: APIArguments) -> Result<APIResponse>;
+
+    /// Sends a GET request to the given URL.
+    fn send_get_request(&mut self, url: &str) -> Result<APIResponse>;
+
+    /// Returns the maximum time a caller repeatedly polling this connection
+    /// (e.g. `Decompilation::wait_until_finished()`) should keep doing so
+    /// before giving up, if configured.
+    fn slow_request_timeout(&self) -> Option<::std::time::Duration> {
+        None
+    }
+}
+
+/// A factory for creating new instances of `APIConnection`.
+pub trait APIConnectionFactory {
+    /// Creates a new connection.
+    fn new_connection(&self) -> Box<APIConnection>;
+}
+
+/// A factory that creates [`HyperAPIConnection`](struct.HyperAPIConnection.html)s.
+pub struct HyperAPIConnectionFactory {
+    settings: Settings,
+    auth: Box<ApiAuth>,
+}
+
+impl HyperAPIConnectionFactory {
+    /// Creates a new factory from the given settings, authenticating with the
+    /// API key from `settings` (if any) via HTTP Basic authentication.
+    pub fn new(settings: Settings) -> Self {
+        let auth = Box::new(ApiKeyAuth::new(
+            settings.api_key().clone().unwrap_or_default(),
+        ));
+        Self::with_auth(settings, auth)
+    }
+
+    /// Creates a new factory, authenticating requests with the given `auth`
+    /// instead of the default API-key-based HTTP Basic authentication.
+    pub fn with_auth(settings: Settings, auth: Box<ApiAuth>) -> Self {
+        HyperAPIConnectionFactory {
+            settings: settings,
+            auth: auth,
+        }
+    }
+}
+
+impl APIConnectionFactory for HyperAPIConnectionFactory {
+    fn new_connection(&self) -> Box<APIConnection> {
+        Box::new(HyperAPIConnection::new(self.settings.clone(), self.auth.clone()))
+    }
+}
+
+/// A connection to the retdec.com API built on top of a blocking
+/// `hyper::Client`.
+///
+/// A new client is built from the given `Settings` for every connection
+/// (rather than shared globally), so proxy, TLS, and timeout settings are
+/// honored. If a client cannot be built from the settings (e.g. the proxy
+/// URL is invalid or the certificate file cannot be read), a default
+/// `hyper::Client` without them is used instead.
+pub struct HyperAPIConnection {
+    client: hyper::Client,
+    settings: Settings,
+    auth: Box<ApiAuth>,
+}
+
+impl HyperAPIConnection {
+    fn new(settings: Settings, auth: Box<ApiAuth>) -> Self {
+        let client = build_client(&settings).unwrap_or_else(|_| hyper::Client::new());
+        HyperAPIConnection {
+            client: client,
+            settings: settings,
+            auth: auth,
+        }
+    }
+
+    fn headers(&self, content_type: Option<&str>) -> Headers {
+        let mut headers = Headers::new();
+        headers.set(UserAgent(self.settings.user_agent().to_string()));
+        headers.set_raw(
+            "Authorization",
+            vec![self.auth.authorization_header().into_bytes()],
+        );
+        if let Some(content_type) = content_type {
+            headers.set_raw("Content-Type", vec![content_type.as_bytes().to_vec()]);
+        }
+        if let Some(ref proxy) = *self.settings.proxy() {
+            if let (&Some(ref username), &Some(ref password)) =
+                (proxy.username(), proxy.password())
+            {
+                // `hyper::net::ProxyConfig` (used in `build_client`) has no
+                // way to carry credentials, so they are sent the same way
+                // the reqwest-based async client's `basic_auth` does it: as
+                // a `Proxy-Authorization: Basic ...` header on every request.
+                let credentials = Authorization(Basic {
+                    username: username.clone(),
+                    password: Some(password.clone()),
+                }).to_string();
+                headers.set_raw("Proxy-Authorization", vec![credentials.into_bytes()]);
+            }
+        }
+        headers
+    }
+
+    fn send(
+        &mut self,
+        request: hyper::client::RequestBuilder,
+        content_type: Option<&str>,
+    ) -> Result<APIResponse> {
+        let mut response = request
+            .headers(self.headers(content_type))
+            .send()
+            .chain_err(|| "failed to send a request to the retdec.com API")?;
+        let mut body = Vec::new();
+        response.read_to_end(&mut body)?;
+        Ok(APIResponse::new(response.status.to_u16(), body))
+    }
+}
+
+/// Builds a `hyper::Client` configured according to the given settings
+/// (proxy, connect/request timeouts, extra trusted root certificate).
+///
+/// A fresh client is built for every connection (rather than reused from a
+/// global) so that per-`Settings` transport configuration is respected. When
+/// this returns an error, `HyperAPIConnection::new` uses a default client
+/// instead.
+fn build_client(settings: &Settings) -> Result<hyper::Client> {
+    let mut tls_builder = ::native_tls::TlsConnector::builder();
+    if let Some(ref ssl_cert_file) = *settings.ssl_cert_file() {
+        use std::fs;
+        let cert_bytes = fs::read(ssl_cert_file)?;
+        let cert = ::native_tls::Certificate::from_pem(&cert_bytes)?;
+        tls_builder.add_root_certificate(cert);
+    }
+    let ssl = NativeTlsClient::from(tls_builder.build()?);
+    let connector = HttpsConnector::new(ssl);
+
+    let mut client = if let Some(ref proxy) = *settings.proxy() {
+        let (scheme, host, port) = parse_proxy_target(proxy.url())?;
+        hyper::Client::with_proxy_config(hyper::net::ProxyConfig::new(
+            &scheme,
+            host,
+            port,
+            connector,
+            HttpsConnector::new(NativeTlsClient::new()?),
+        ))
+    } else {
+        hyper::Client::with_connector(connector)
+    };
+
+    // hyper 0.10's blocking client only exposes read/write socket timeouts,
+    // not a separate connect timeout; the read timeout covers the time spent
+    // waiting for the connection to be established as well as for the
+    // response, so `request_timeout` (which needs to bound the whole
+    // round trip) is applied to both, falling back to `connect_timeout` when
+    // it is the only one given.
+    let timeout = settings.request_timeout().or_else(|| settings.connect_timeout());
+    client.set_read_timeout(timeout);
+    client.set_write_timeout(timeout);
+
+    Ok(client)
+}
+
+/// Splits a proxy URL such as `http://proxy.example.com:8080` into the
+/// scheme, host, and port expected by `hyper::net::ProxyConfig::new`.
+fn parse_proxy_target(url: &str) -> Result<(String, String, u16)> {
+    let parsed = Url::parse(url).chain_err(|| format!("invalid proxy URL: {}", url))?;
+    let scheme = parsed.scheme().to_string();
+    let host = match parsed.host_str() {
+        Some(host) => host.to_string(),
+        None => bail!("proxy URL has no host: {}", url),
+    };
+    let port = match parsed.port_or_known_default() {
+        Some(port) => port,
+        None => bail!("proxy URL has no port: {}", url),
+    };
+    Ok((scheme, host, port))
+}
+
+impl APIConnection for HyperAPIConnection {
+    fn api_url(&self) -> String {
+        self.settings.api_url()
+    }
+
+    fn send_post_request(&mut self, url: &str, args: APIArguments) -> Result<APIResponse> {
+        let url = url.into_url()?;
+        let boundary = generate_multipart_boundary(&args);
+        let body = encode_multipart_body(&args, &boundary);
+        let content_type = format!("multipart/form-data; boundary=\"{}\"", boundary);
+        self.send(
+            self.client.post(url).body(&body[..]),
+            Some(&content_type),
+        )
+    }
+
+    fn send_get_request(&mut self, url: &str) -> Result<APIResponse> {
+        let url = url.into_url()?;
+        self.send(self.client.get(url), None)
+    }
+
+    fn slow_request_timeout(&self) -> Option<::std::time::Duration> {
+        self.settings.slow_request_timeout()
+    }
+}
+
+/// Generates a boundary for a `multipart/form-data` body that does not occur
+/// in the given arguments, mirroring what `reqwest`'s multipart builder does
+/// for the async client.
+fn generate_multipart_boundary(args: &APIArguments) -> String {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    let mut hasher = DefaultHasher::new();
+    args.string_args.hash(&mut hasher);
+    for &(ref name, ref file) in &args.file_args {
+        name.hash(&mut hasher);
+        file.name().hash(&mut hasher);
+        file.content().hash(&mut hasher);
+    }
+    COUNTER.fetch_add(1, Ordering::Relaxed).hash(&mut hasher);
+
+    format!("retdec-rust-{:016x}", hasher.finish())
+}
+
+/// Encodes the given arguments as a `multipart/form-data` body, the way
+/// `reqwest`'s multipart builder encodes `APIArguments::into_multipart()`
+/// for the async client (that method is defined in `async_connection`).
+fn encode_multipart_body(args: &APIArguments, boundary: &str) -> Vec<u8> {
+    let mut body = Vec::new();
+    for &(ref name, ref value) in &args.string_args {
+        body.extend_from_slice(format!("--{}\r\n", boundary).as_bytes());
+        body.extend_from_slice(
+            format!("Content-Disposition: form-data; name=\"{}\"\r\n\r\n", name).as_bytes(),
+        );
+        body.extend_from_slice(value.as_bytes());
+        body.extend_from_slice(b"\r\n");
+    }
+    for &(ref name, ref file) in &args.file_args {
+        body.extend_from_slice(format!("--{}\r\n", boundary).as_bytes());
+        body.extend_from_slice(
+            format!(
+                "Content-Disposition: form-data; name=\"{}\"; filename=\"{}\"\r\n",
+                name,
+                file.name()
+            ).as_bytes(),
+        );
+        body.extend_from_slice(b"Content-Type: application/octet-stream\r\n\r\n");
+        body.extend_from_slice(file.content());
+        body.extend_from_slice(b"\r\n");
+    }
+    body.extend_from_slice(format!("--{}--\r\n", boundary).as_bytes());
+    body
+}
+
+/// A connection factory that wraps another factory and verifies that every
+/// response it receives has a successful (2xx) status code, turning
+/// unsuccessful ones into errors.
+pub struct ResponseVerifyingAPIConnectionFactory {
+    inner: Box<APIConnectionFactory>,
+}
+
+impl ResponseVerifyingAPIConnectionFactory {
+    /// Wraps the given factory.
+    pub fn new(inner: Box<APIConnectionFactory>) -> Self {
+        ResponseVerifyingAPIConnectionFactory { inner: inner }
+    }
+}
+
+impl APIConnectionFactory for ResponseVerifyingAPIConnectionFactory {
+    fn new_connection(&self) -> Box<APIConnection> {
+        Box::new(ResponseVerifyingAPIConnection::new(
+            self.inner.new_connection(),
+        ))
+    }
+}
+
+struct ResponseVerifyingAPIConnection {
+    inner: Box<APIConnection>,
+}
+
+impl ResponseVerifyingAPIConnection {
+    fn new(inner: Box<APIConnection>) -> Self {
+        ResponseVerifyingAPIConnection { inner: inner }
+    }
+
+    fn verify(response: APIResponse) -> Result<APIResponse> {
+        if response.status_code() < 200 || response.status_code() >= 300 {
+            bail!(
+                "the retdec.com API returned an unsuccessful status code: {}",
+                response.status_code()
+            );
+        }
+        Ok(response)
+    }
+}
+
+impl APIConnection for ResponseVerifyingAPIConnection {
+    fn api_url(&self) -> String {
+        self.inner.api_url()
+    }
+
+    fn send_post_request(&mut self, url: &str, args: APIArguments) -> Result<APIResponse> {
+        self.inner.send_post_request(url, args).and_then(Self::verify)
+    }
+
+    fn send_get_request(&mut self, url: &str) -> Result<APIResponse> {
+        self.inner.send_get_request(url).and_then(Self::verify)
+    }
+
+    fn slow_request_timeout(&self) -> Option<::std::time::Duration> {
+        self.inner.slow_request_timeout()
+    }
+}
+
+/// Mocks for testing code that depends on `APIConnection`/`APIConnectionFactory`.
+#[cfg(test)]
+pub mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use super::*;
+
+    /// A builder for `APIArguments`, used to express expectations in tests.
+    #[derive(Default)]
+    pub struct APIArgumentsBuilder {
+        args: APIArguments,
+    }
+
+    impl APIArgumentsBuilder {
+        pub fn new() -> Self {
+            APIArgumentsBuilder::default()
+        }
+
+        pub fn with_string_arg<S: Into<String>>(mut self, name: &str, value: S) -> Self {
+            self.args.add_string_arg(name, value);
+            self
+        }
+
+        pub fn with_file(mut self, name: &str, file: File) -> Self {
+            self.args.add_file(name, file);
+            self
+        }
+
+        pub fn build(self) -> APIArguments {
+            self.args
+        }
+    }
+
+    /// A builder for `APIResponse`.
+    #[derive(Default)]
+    pub struct APIResponseBuilder {
+        status_code: u16,
+        body: Vec<u8>,
+    }
+
+    impl APIResponseBuilder {
+        pub fn new() -> Self {
+            APIResponseBuilder::default()
+        }
+
+        pub fn with_status_code(mut self, status_code: u16) -> Self {
+            self.status_code = status_code;
+            self
+        }
+
+        pub fn with_body(mut self, body: &[u8]) -> Self {
+            self.body = body.to_vec();
+            self
+        }
+
+        pub fn build(self) -> APIResponse {
+            APIResponse::new(self.status_code, self.body)
+        }
+    }
+
+    /// A mock `APIConnection` that records sent requests and replays
+    /// pre-programmed responses.
+    pub struct APIConnectionMock {
+        settings: Settings,
+        responses: Vec<(String, String, Result<APIResponse>)>,
+        requests: Vec<(String, String, APIArguments)>,
+    }
+
+    impl APIConnectionMock {
+        pub fn new(settings: Settings) -> Self {
+            APIConnectionMock {
+                settings: settings,
+                responses: Vec::new(),
+                requests: Vec::new(),
+            }
+        }
+
+        pub fn add_response(&mut self, method: &str, url: &str, response: Result<APIResponse>) {
+            self.responses
+                .push((method.to_string(), url.to_string(), response));
+        }
+
+        pub fn request_sent(&self, method: &str, url: &str, args: APIArguments) -> bool {
+            self.requests.iter().any(|&(ref m, ref u, ref a)| {
+                m == method && u == url && *a == args
+            })
+        }
+
+        fn next_response(&mut self, method: &str, url: &str) -> Result<APIResponse> {
+            let index = self.responses
+                .iter()
+                .position(|&(ref m, ref u, _)| m == method && u == url)
+                .unwrap_or_else(|| panic!("no response set up for {} {}", method, url));
+            let (_, _, response) = self.responses.remove(index);
+            response
+        }
+    }
+
+    impl APIConnection for APIConnectionMock {
+        fn api_url(&self) -> String {
+            self.settings.api_url()
+        }
+
+        fn send_post_request(&mut self, url: &str, args: APIArguments) -> Result<APIResponse> {
+            self.requests
+                .push(("POST".to_string(), url.to_string(), args));
+            self.next_response("POST", url)
+        }
+
+        fn send_get_request(&mut self, url: &str) -> Result<APIResponse> {
+            self.requests.push(("GET".to_string(), url.to_string(), APIArguments::new()));
+            self.next_response("GET", url)
+        }
+    }
+
+    /// A factory that always returns the same, shared mock connection.
+    pub struct APIConnectionFactoryMock {
+        conn: Rc<RefCell<APIConnectionMock>>,
+    }
+
+    impl APIConnectionFactoryMock {
+        pub fn new(conn: Rc<RefCell<APIConnectionMock>>) -> Self {
+            APIConnectionFactoryMock { conn: conn }
+        }
+    }
+
+    impl APIConnectionFactory for APIConnectionFactoryMock {
+        fn new_connection(&self) -> Box<APIConnection> {
+            Box::new(SharedAPIConnectionMock {
+                conn: self.conn.clone(),
+            })
+        }
+    }
+
+    struct SharedAPIConnectionMock {
+        conn: Rc<RefCell<APIConnectionMock>>,
+    }
+
+    impl APIConnection for SharedAPIConnectionMock {
+        fn api_url(&self) -> String {
+            self.conn.borrow().api_url()
+        }
+
+        fn send_post_request(&mut self, url: &str, args: APIArguments) -> Result<APIResponse> {
+            self.conn.borrow_mut().send_post_request(url, args)
+        }
+
+        fn send_get_request(&mut self, url: &str) -> Result<APIResponse> {
+            self.conn.borrow_mut().send_get_request(url)
+        }
+    }
+}
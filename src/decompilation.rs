@@ -0,0 +1,493 @@
+//! Access to a running or finished decompilation.
+
+use std::collections::VecDeque;
+use std::thread;
+use std::time::Duration;
+
+use serde_json::Value;
+
+use connection::APIArguments;
+use connection::APIConnection;
+use error::Result;
+use file::File;
+
+/// Default interval (in seconds) between two consecutive polls of a
+/// decompilation's status while streaming its phases.
+const DEFAULT_POLL_INTERVAL_SECS: u64 = 5;
+
+/// Default number of consecutive transient HTTP errors that
+/// [`DecompilationPhases`](struct.DecompilationPhases.html) tolerates before
+/// giving up and ending the iteration.
+const DEFAULT_MAX_CONSECUTIVE_ERRORS: u32 = 3;
+
+/// Arguments for a decompilation.
+///
+/// # Examples
+///
+/// ```
+/// use retdec::decompilation::DecompilationArguments;
+/// use retdec::file::File;
+///
+/// let args = DecompilationArguments::new()
+///     .with_input_file(File::from_path("file.exe").unwrap())
+///     .with_mode("bin")
+///     .with_target_language("c");
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct DecompilationArguments {
+    input_file: Option<File>,
+    mode: Option<String>,
+    target_language: Option<String>,
+    architecture: Option<String>,
+    endian: Option<String>,
+    raw_entry_point: Option<String>,
+    raw_section_vma: Option<String>,
+    file_format: Option<String>,
+    decomp_optimizations: Option<String>,
+    selective_decompilation_ranges: Option<String>,
+    output_format: Option<String>,
+}
+
+impl DecompilationArguments {
+    /// Creates new arguments with no input file and the default mode (`bin`).
+    pub fn new() -> Self {
+        DecompilationArguments::default()
+    }
+
+    /// Sets the input file to be decompiled.
+    pub fn with_input_file(mut self, input_file: File) -> Self {
+        self.input_file = Some(input_file);
+        self
+    }
+
+    /// Returns the input file, taking ownership of it.
+    pub fn take_input_file(&mut self) -> Option<File> {
+        self.input_file.take()
+    }
+
+    /// Sets the decompilation mode (`bin`, `c`, or `raw`).
+    ///
+    /// When not set, `bin` is used.
+    pub fn with_mode<S: Into<String>>(mut self, mode: S) -> Self {
+        self.mode = Some(mode.into());
+        self
+    }
+
+    /// Returns the decompilation mode, defaulting to `bin` when unset.
+    pub fn mode(&self) -> &str {
+        self.mode.as_ref().map(String::as_str).unwrap_or("bin")
+    }
+
+    /// Sets the target high-level language of the decompilation (e.g. `c` or
+    /// `py`).
+    pub fn with_target_language<S: Into<String>>(mut self, target_language: S) -> Self {
+        self.target_language = Some(target_language.into());
+        self
+    }
+
+    /// Returns the target high-level language, if any.
+    pub fn target_language(&self) -> &Option<String> {
+        &self.target_language
+    }
+
+    /// Sets the architecture of the input file.
+    ///
+    /// Required when `mode` is `raw`.
+    pub fn with_architecture<S: Into<String>>(mut self, architecture: S) -> Self {
+        self.architecture = Some(architecture.into());
+        self
+    }
+
+    /// Returns the architecture, if any.
+    pub fn architecture(&self) -> &Option<String> {
+        &self.architecture
+    }
+
+    /// Sets the endianness of the input file (`little` or `big`).
+    ///
+    /// Required when `mode` is `raw`.
+    pub fn with_endian<S: Into<String>>(mut self, endian: S) -> Self {
+        self.endian = Some(endian.into());
+        self
+    }
+
+    /// Returns the endianness, if any.
+    pub fn endian(&self) -> &Option<String> {
+        &self.endian
+    }
+
+    /// Sets the address (in hexadecimal, e.g. `0x1000`) at which execution of
+    /// the raw input file starts.
+    ///
+    /// Only meaningful when `mode` is `raw`.
+    pub fn with_raw_entry_point<S: Into<String>>(mut self, raw_entry_point: S) -> Self {
+        self.raw_entry_point = Some(raw_entry_point.into());
+        self
+    }
+
+    /// Returns the raw entry point, if any.
+    pub fn raw_entry_point(&self) -> &Option<String> {
+        &self.raw_entry_point
+    }
+
+    /// Sets the virtual memory address (in hexadecimal, e.g. `0x1000`) at
+    /// which the raw input file is to be loaded.
+    ///
+    /// Only meaningful when `mode` is `raw`.
+    pub fn with_raw_section_vma<S: Into<String>>(mut self, raw_section_vma: S) -> Self {
+        self.raw_section_vma = Some(raw_section_vma.into());
+        self
+    }
+
+    /// Returns the raw section virtual memory address, if any.
+    pub fn raw_section_vma(&self) -> &Option<String> {
+        &self.raw_section_vma
+    }
+
+    /// Sets the format of the raw input file (e.g. `elf`, `pe`, `coff`).
+    ///
+    /// Only meaningful when `mode` is `raw`, since there is no container to
+    /// detect it from. Unlike the architecture and endianness, it is not
+    /// required in `raw` mode.
+    pub fn with_file_format<S: Into<String>>(mut self, file_format: S) -> Self {
+        self.file_format = Some(file_format.into());
+        self
+    }
+
+    /// Returns the raw file format, if any.
+    pub fn file_format(&self) -> &Option<String> {
+        &self.file_format
+    }
+
+    /// Sets the level of decompilation optimizations to be performed (e.g.
+    /// `none` or `default`).
+    pub fn with_decomp_optimizations<S: Into<String>>(mut self, decomp_optimizations: S) -> Self {
+        self.decomp_optimizations = Some(decomp_optimizations.into());
+        self
+    }
+
+    /// Returns the requested decompilation optimizations, if any.
+    pub fn decomp_optimizations(&self) -> &Option<String> {
+        &self.decomp_optimizations
+    }
+
+    /// Sets the address ranges (e.g. `0x1000-0x2000`) to be selectively
+    /// decompiled.
+    pub fn with_selective_decompilation_ranges<S: Into<String>>(
+        mut self,
+        selective_decompilation_ranges: S,
+    ) -> Self {
+        self.selective_decompilation_ranges = Some(selective_decompilation_ranges.into());
+        self
+    }
+
+    /// Returns the selective-decompilation address ranges, if any.
+    pub fn selective_decompilation_ranges(&self) -> &Option<String> {
+        &self.selective_decompilation_ranges
+    }
+
+    /// Sets the format in which the decompiled output is to be generated
+    /// (e.g. `plain` or `json_human_readable`).
+    pub fn with_output_format<S: Into<String>>(mut self, output_format: S) -> Self {
+        self.output_format = Some(output_format.into());
+        self
+    }
+
+    /// Returns the requested output format, if any.
+    pub fn output_format(&self) -> &Option<String> {
+        &self.output_format
+    }
+
+    /// Validates the arguments and turns them into the `APIArguments` to be
+    /// sent to the decompilation-starting endpoint.
+    ///
+    /// Shared by [`Decompiler`](../decompiler/struct.Decompiler.html) and
+    /// [`AsyncDecompiler`](../async_decompiler/struct.AsyncDecompiler.html)
+    /// so that both paths always send the same set of parameters.
+    pub(crate) fn into_api_args(mut self) -> Result<APIArguments> {
+        if self.mode() == "raw" && (self.architecture().is_none() || self.endian().is_none()) {
+            bail!("raw mode requires both an architecture and an endian to be given");
+        }
+
+        let mut api_args = APIArguments::new();
+        api_args.add_string_arg("mode", self.mode());
+        api_args.add_opt_string_arg("target_language", self.target_language());
+        api_args.add_opt_string_arg("architecture", self.architecture());
+        api_args.add_opt_string_arg("endian", self.endian());
+        api_args.add_opt_string_arg("raw_entry_point", self.raw_entry_point());
+        api_args.add_opt_string_arg("raw_section_vma", self.raw_section_vma());
+        api_args.add_opt_string_arg("file_format", self.file_format());
+        api_args.add_opt_string_arg("decomp_optimizations", self.decomp_optimizations());
+        api_args.add_opt_string_arg(
+            "selective_decompilation_ranges",
+            self.selective_decompilation_ranges(),
+        );
+        api_args.add_opt_string_arg("output_format", self.output_format());
+        match self.take_input_file() {
+            Some(input_file) => {
+                api_args.add_file("input", input_file);
+            }
+            None => {
+                bail!("no input file given");
+            }
+        }
+        Ok(api_args)
+    }
+}
+
+/// A running or finished decompilation.
+pub struct Decompilation {
+    id: String,
+    conn: Box<APIConnection>,
+}
+
+impl Decompilation {
+    /// Creates access to a decompilation with the given ID over the given
+    /// connection.
+    pub fn new(id: String, conn: Box<APIConnection>) -> Self {
+        Decompilation { id: id, conn: conn }
+    }
+
+    /// Returns the ID of the decompilation.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Has the decompilation finished?
+    pub fn has_finished(&mut self) -> Result<bool> {
+        let url = format!(
+            "{}/decompiler/decompilations/{}/status",
+            self.conn.api_url(),
+            self.id
+        );
+        let response = self.conn.send_get_request(&url)?;
+        Ok(response.json_value_as_bool("finished").unwrap_or(false))
+    }
+
+    /// Blocks until the decompilation is finished.
+    ///
+    /// If `Settings::with_slow_request_timeout()` was used to configure the
+    /// connection, polling gives up with an error once that much time has
+    /// elapsed without the decompilation finishing, rather than hanging
+    /// forever against an unresponsive endpoint.
+    pub fn wait_until_finished(&mut self) -> Result<()> {
+        let deadline = self.conn
+            .slow_request_timeout()
+            .map(|timeout| ::std::time::Instant::now() + timeout);
+
+        while !self.has_finished()? {
+            if let Some(deadline) = deadline {
+                if ::std::time::Instant::now() >= deadline {
+                    bail!("timed out while waiting for the decompilation to finish");
+                }
+            }
+            ::std::thread::sleep(Duration::from_secs(DEFAULT_POLL_INTERVAL_SECS));
+        }
+        Ok(())
+    }
+
+    /// Obtains and returns the decompiled code in the requested
+    /// high-level-language format.
+    pub fn get_output_hll_code(&mut self) -> Result<String> {
+        let url = format!(
+            "{}/decompiler/decompilations/{}/outputs/hll",
+            self.conn.api_url(),
+            self.id
+        );
+        let response = self.conn.send_get_request(&url)?;
+        Ok(response.body_as_string()?)
+    }
+
+    /// Returns a pull-based iterator over the phases (and log messages) the
+    /// decompilation goes through, without blocking until it is finished.
+    ///
+    /// Unlike [`wait_until_finished()`](#method.wait_until_finished), this
+    /// lets callers observe progress as it happens, e.g. to print it or to
+    /// forward it to their own logging. Consumes `self`, as the returned
+    /// iterator takes over polling the decompilation.
+    pub fn phases(self) -> DecompilationPhases {
+        DecompilationPhases::new(self.id, self.conn)
+    }
+}
+
+/// A single phase (or log message) reported while a decompilation is
+/// running.
+#[derive(Clone, Debug)]
+pub struct DecompilationPhase {
+    index: i64,
+    name: String,
+    description: Option<String>,
+}
+
+impl DecompilationPhase {
+    /// Returns the index of the phase within the decompilation, in the order
+    /// it was reported.
+    pub fn index(&self) -> i64 {
+        self.index
+    }
+
+    /// Returns the name of the phase.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the human-readable description of the phase, if any.
+    pub fn description(&self) -> &Option<String> {
+        &self.description
+    }
+}
+
+/// A pull-based iterator that tails the phases (and log messages) of a
+/// running decompilation, modeled on tailing an append-only event stream.
+///
+/// Each call to `next()` issues a GET request to the decompilation's status
+/// endpoint, parses the `completion` percentage and the `phases`/`log`
+/// array, and yields any entries with an index greater than the ones
+/// already yielded. Once the status reports `finished == true`, the
+/// remaining entries are yielded and the iterator then returns `None`.
+///
+/// Transient HTTP errors are surfaced as `Some(Err(..))` without ending the
+/// iteration, unless more than `max_consecutive_errors` errors occur in a
+/// row, in which case the iterator gives up and returns `None`. A poll interval is
+/// applied between requests that yield nothing new, so the loop does not
+/// hammer the server.
+pub struct DecompilationPhases {
+    id: String,
+    conn: Box<APIConnection>,
+    pending: VecDeque<DecompilationPhase>,
+    last_phase_index: i64,
+    last_log_index: i64,
+    completion: u32,
+    finished: bool,
+    consecutive_errors: u32,
+    max_consecutive_errors: u32,
+    poll_interval: Duration,
+}
+
+impl DecompilationPhases {
+    fn new(id: String, conn: Box<APIConnection>) -> Self {
+        DecompilationPhases {
+            id: id,
+            conn: conn,
+            pending: VecDeque::new(),
+            last_phase_index: -1,
+            last_log_index: -1,
+            completion: 0,
+            finished: false,
+            consecutive_errors: 0,
+            max_consecutive_errors: DEFAULT_MAX_CONSECUTIVE_ERRORS,
+            poll_interval: Duration::from_secs(DEFAULT_POLL_INTERVAL_SECS),
+        }
+    }
+
+    /// Overrides how many consecutive transient errors are tolerated before
+    /// the iterator gives up.
+    pub fn with_max_consecutive_errors(mut self, max_consecutive_errors: u32) -> Self {
+        self.max_consecutive_errors = max_consecutive_errors;
+        self
+    }
+
+    /// Overrides the interval waited between polls that yield nothing new.
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Returns the completion percentage (0-100) reported by the last poll
+    /// of the decompilation's status.
+    pub fn completion(&self) -> u32 {
+        self.completion
+    }
+
+    /// Polls the status endpoint once, updating `finished` and returning any
+    /// phase/log entries not yet seen.
+    ///
+    /// `phases` and `log` are independent, append-only streams, each with
+    /// its own index sequence, so each is tracked with its own cursor;
+    /// otherwise log entries sharing an index already claimed by a phase
+    /// (or vice versa) would be dropped forever.
+    fn poll(&mut self) -> Result<Vec<DecompilationPhase>> {
+        let url = format!(
+            "{}/decompiler/decompilations/{}/status",
+            self.conn.api_url(),
+            self.id
+        );
+        let response = self.conn.send_get_request(&url)?;
+        let body = response.body_as_json()?;
+
+        self.finished = body["finished"].as_bool().unwrap_or(false);
+        self.completion = body["completion"].as_u64().unwrap_or(0) as u32;
+
+        let mut new_phases = Self::new_entries(&body, "phases", &mut self.last_phase_index);
+        new_phases.extend(Self::new_entries(&body, "log", &mut self.last_log_index));
+        new_phases.sort_by_key(|phase| phase.index);
+        Ok(new_phases)
+    }
+
+    /// Returns the entries under `body[entries_key]` with an index greater
+    /// than `last_index`, advancing `last_index` to the highest one found.
+    fn new_entries(body: &Value, entries_key: &str, last_index: &mut i64) -> Vec<DecompilationPhase> {
+        let mut new_entries = Vec::new();
+        let entries = match body[entries_key].as_array() {
+            Some(entries) => entries,
+            None => return new_entries,
+        };
+        for entry in entries {
+            let index = match entry["index"].as_i64() {
+                Some(index) => index,
+                None => continue,
+            };
+            if index <= *last_index {
+                continue;
+            }
+            new_entries.push(DecompilationPhase {
+                index: index,
+                name: entry["name"].as_str().unwrap_or("").to_string(),
+                description: entry["description"].as_str().map(String::from),
+            });
+        }
+        if let Some(max_index) = new_entries.iter().map(|entry| entry.index).max() {
+            *last_index = max_index;
+        }
+        new_entries
+    }
+}
+
+impl Iterator for DecompilationPhases {
+    type Item = Result<DecompilationPhase>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(phase) = self.pending.pop_front() {
+                return Some(Ok(phase));
+            }
+            if self.finished {
+                return None;
+            }
+
+            match self.poll() {
+                Ok(new_phases) => {
+                    self.consecutive_errors = 0;
+                    if new_phases.is_empty() {
+                        if self.finished {
+                            return None;
+                        }
+                        thread::sleep(self.poll_interval);
+                        continue;
+                    }
+                    self.pending.extend(new_phases);
+                }
+                Err(err) => {
+                    self.consecutive_errors += 1;
+                    if self.consecutive_errors > self.max_consecutive_errors {
+                        return None;
+                    }
+                    // Back off a bit longer after an error than after a
+                    // plain empty poll, so a flaky endpoint isn't hammered.
+                    thread::sleep(self.poll_interval * 2);
+                    return Some(Err(err));
+                }
+            }
+        }
+    }
+}
@@ -1,7 +1,8 @@
 //! Access to the file-decompiling service
 //! ([decompiler](https://retdec.com/api/docs/decompiler.html)).
 
-use connection::APIArguments;
+use auth::ApiAuth;
+use auth::ApiKeyAuth;
 use connection::APIConnectionFactory;
 use connection::HyperAPIConnectionFactory;
 use connection::ResponseVerifyingAPIConnectionFactory;
@@ -27,7 +28,9 @@ use settings::Settings;
 ///     .with_api_key("MY-API-KEY");
 /// let decompiler = Decompiler::new(settings);
 /// let args = DecompilationArguments::new()
-///     .with_input_file(File::from_path("file.exe")?);
+///     .with_input_file(File::from_path("file.exe")?)
+///     .with_mode("bin")
+///     .with_target_language("c");
 /// let mut decompilation = decompiler.start_decompilation(args)?;
 /// decompilation.wait_until_finished()?;
 /// let output_code = decompilation.get_output_hll_code()?;
@@ -39,12 +42,28 @@ pub struct Decompiler {
 }
 
 impl Decompiler {
-    /// Creates a new instance of the file-decompiling service.
+    /// Creates a new instance of the file-decompiling service, authenticating
+    /// with the API key from `settings` (if any) via HTTP Basic
+    /// authentication.
     pub fn new(settings: Settings) -> Self {
+        let auth = Box::new(ApiKeyAuth::new(
+            settings.api_key().clone().unwrap_or_default(),
+        ));
+        Self::with_auth(settings, auth)
+    }
+
+    /// Creates a new instance of the file-decompiling service, authenticating
+    /// requests with the given `auth` instead of the default API-key-based
+    /// HTTP Basic authentication.
+    ///
+    /// This allows credentials to be sourced from elsewhere (e.g. a Bearer
+    /// token, or a key refreshed at runtime) without reconstructing the
+    /// whole client.
+    pub fn with_auth(settings: Settings, auth: Box<ApiAuth>) -> Self {
         Decompiler {
             conn_factory: Box::new(
                 ResponseVerifyingAPIConnectionFactory::new(
-                    Box::new(HyperAPIConnectionFactory::new(settings))
+                    Box::new(HyperAPIConnectionFactory::with_auth(settings, auth))
                 )
             ),
         }
@@ -54,7 +73,7 @@ impl Decompiler {
     pub fn start_decompilation(&self, args: DecompilationArguments) -> Result<Decompilation> {
         let mut conn = self.conn_factory.new_connection();
         let url = format!("{}/decompiler/decompilations", conn.api_url());
-        let api_args = self.create_api_args(args)?;
+        let api_args = args.into_api_args()?;
         let response = conn.send_post_request(&url, api_args)
             .chain_err(|| "failed to start a decompilation")?;
         let id = response.json_value_as_string("id")
@@ -62,20 +81,6 @@ impl Decompiler {
         Ok(Decompilation::new(id, conn))
     }
 
-    fn create_api_args(&self, mut args: DecompilationArguments) -> Result<APIArguments> {
-        let mut api_args = APIArguments::new();
-        api_args.add_string_arg("mode", "bin");
-        match args.take_input_file() {
-            Some(input_file) => {
-                api_args.add_file("input", input_file);
-            }
-            None => {
-                bail!("no input file given");
-            }
-        }
-        Ok(api_args)
-    }
-
     #[cfg(test)]
     fn with_conn_factory(conn_factory: Box<APIConnectionFactory>) -> Self {
         Decompiler { conn_factory: conn_factory }
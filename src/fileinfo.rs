@@ -3,6 +3,8 @@
 
 use analysis::Analysis;
 use analysis::AnalysisArguments;
+use auth::ApiAuth;
+use auth::ApiKeyAuth;
 use connection::APIArguments;
 use connection::APIConnectionFactory;
 use connection::HyperAPIConnectionFactory;
@@ -36,10 +38,22 @@ pub struct Fileinfo {
 }
 
 impl Fileinfo {
-    /// Creates a new instance of the file-analyzing service.
+    /// Creates a new instance of the file-analyzing service, authenticating
+    /// with the API key from `settings` (if any) via HTTP Basic
+    /// authentication.
     pub fn new(settings: Settings) -> Self {
+        let auth = Box::new(ApiKeyAuth::new(
+            settings.api_key().clone().unwrap_or_default(),
+        ));
+        Self::with_auth(settings, auth)
+    }
+
+    /// Creates a new instance of the file-analyzing service, authenticating
+    /// requests with the given `auth` instead of the default API-key-based
+    /// HTTP Basic authentication.
+    pub fn with_auth(settings: Settings, auth: Box<ApiAuth>) -> Self {
         Fileinfo {
-            conn_factory: Box::new(HyperAPIConnectionFactory::new(settings)),
+            conn_factory: Box::new(HyperAPIConnectionFactory::with_auth(settings, auth)),
         }
     }
 
@@ -0,0 +1,242 @@
+//! Settings for accessing the retdec.com API.
+
+use std::env;
+use std::time::Duration;
+
+const DEFAULT_API_URL: &str = "https://retdec.com/service/api";
+const API_URL_ENV_VAR: &str = "RETDEC_API_URL";
+const DEFAULT_USER_AGENT: &str = concat!("retdec-rust/", env!("CARGO_PKG_VERSION"));
+
+/// Proxy to be used when connecting to the retdec.com API.
+#[derive(Clone, Debug)]
+pub struct Proxy {
+    url: String,
+    username: Option<String>,
+    password: Option<String>,
+}
+
+impl Proxy {
+    /// Creates a new proxy pointing at the given URL (e.g.
+    /// `http://proxy.example.com:8080`).
+    pub fn new<S: Into<String>>(url: S) -> Self {
+        Proxy {
+            url: url.into(),
+            username: None,
+            password: None,
+        }
+    }
+
+    /// Sets credentials to authenticate with the proxy.
+    pub fn with_credentials<S: Into<String>>(mut self, username: S, password: S) -> Self {
+        self.username = Some(username.into());
+        self.password = Some(password.into());
+        self
+    }
+
+    /// Returns the URL of the proxy.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Returns the username to authenticate with the proxy, if any.
+    pub fn username(&self) -> &Option<String> {
+        &self.username
+    }
+
+    /// Returns the password to authenticate with the proxy, if any.
+    pub fn password(&self) -> &Option<String> {
+        &self.password
+    }
+}
+
+/// Settings for accessing the retdec.com API.
+///
+/// # Examples
+///
+/// ```
+/// use retdec::settings::Settings;
+///
+/// let settings = Settings::new()
+///     .with_api_key("MY-API-KEY");
+/// ```
+#[derive(Clone, Debug)]
+pub struct Settings {
+    api_url: Option<String>,
+    api_key: Option<String>,
+    proxy: Option<Proxy>,
+    connect_timeout: Option<Duration>,
+    request_timeout: Option<Duration>,
+    slow_request_timeout: Option<Duration>,
+    ssl_cert_file: Option<String>,
+    user_agent: Option<String>,
+}
+
+impl Settings {
+    /// Creates new, default settings.
+    pub fn new() -> Self {
+        Settings {
+            api_url: None,
+            api_key: None,
+            proxy: None,
+            connect_timeout: None,
+            request_timeout: None,
+            slow_request_timeout: None,
+            ssl_cert_file: None,
+            user_agent: None,
+        }
+    }
+
+    /// Sets the URL to the retdec.com API.
+    ///
+    /// When not set, the value of the `RETDEC_API_URL` environment variable
+    /// is used when present, and `https://retdec.com/service/api` otherwise.
+    pub fn with_api_url<S: Into<String>>(mut self, api_url: S) -> Self {
+        self.api_url = Some(api_url.into());
+        self
+    }
+
+    /// Returns the URL to the retdec.com API.
+    pub fn api_url(&self) -> String {
+        self.api_url
+            .clone()
+            .or_else(|| env::var(API_URL_ENV_VAR).ok())
+            .unwrap_or_else(|| DEFAULT_API_URL.to_string())
+    }
+
+    /// Sets the API key to be used for authentication.
+    pub fn with_api_key<S: Into<String>>(mut self, api_key: S) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+
+    /// Returns the API key, if any.
+    pub fn api_key(&self) -> &Option<String> {
+        &self.api_key
+    }
+
+    /// Sets the HTTP proxy to be used for every request.
+    pub fn with_proxy(mut self, proxy: Proxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Returns the HTTP proxy, if any.
+    pub fn proxy(&self) -> &Option<Proxy> {
+        &self.proxy
+    }
+
+    /// Sets the maximum time allowed to establish a connection to the API.
+    pub fn with_connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Returns the connect timeout, if any.
+    pub fn connect_timeout(&self) -> Option<Duration> {
+        self.connect_timeout
+    }
+
+    /// Sets the maximum time allowed for a single request/response
+    /// round-trip (e.g. starting a decompilation or polling its status).
+    pub fn with_request_timeout(mut self, timeout: Duration) -> Self {
+        self.request_timeout = Some(timeout);
+        self
+    }
+
+    /// Returns the request timeout, if any.
+    pub fn request_timeout(&self) -> Option<Duration> {
+        self.request_timeout
+    }
+
+    /// Sets the maximum time `wait_until_finished()` is allowed to keep
+    /// polling the API for before giving up with an error.
+    ///
+    /// Without this, an unresponsive endpoint could otherwise make
+    /// `wait_until_finished()` hang forever.
+    pub fn with_slow_request_timeout(mut self, timeout: Duration) -> Self {
+        self.slow_request_timeout = Some(timeout);
+        self
+    }
+
+    /// Returns the slow-request timeout, if any.
+    pub fn slow_request_timeout(&self) -> Option<Duration> {
+        self.slow_request_timeout
+    }
+
+    /// Sets an additional PEM-encoded root certificate to trust, for users
+    /// behind a TLS-inspecting corporate proxy.
+    pub fn with_ssl_cert_file<S: Into<String>>(mut self, ssl_cert_file: S) -> Self {
+        self.ssl_cert_file = Some(ssl_cert_file.into());
+        self
+    }
+
+    /// Returns the path to the additional trusted root certificate, if any.
+    pub fn ssl_cert_file(&self) -> &Option<String> {
+        &self.ssl_cert_file
+    }
+
+    /// Overrides the `User-Agent` header sent with every request.
+    pub fn with_user_agent<S: Into<String>>(mut self, user_agent: S) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// Returns the `User-Agent` header to be sent with every request.
+    pub fn user_agent(&self) -> &str {
+        self.user_agent
+            .as_ref()
+            .map(String::as_str)
+            .unwrap_or(DEFAULT_USER_AGENT)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn settings_have_no_proxy_or_timeouts_by_default() {
+        let settings = Settings::new();
+
+        assert!(settings.proxy().is_none());
+        assert!(settings.connect_timeout().is_none());
+        assert!(settings.request_timeout().is_none());
+        assert!(settings.slow_request_timeout().is_none());
+        assert!(settings.ssl_cert_file().is_none());
+        assert_eq!(settings.user_agent(), DEFAULT_USER_AGENT);
+    }
+
+    #[test]
+    fn settings_with_proxy_returns_given_proxy() {
+        let proxy = Proxy::new("http://proxy.example.com:8080")
+            .with_credentials("user", "pass");
+        let settings = Settings::new().with_proxy(proxy);
+
+        let proxy = settings.proxy().as_ref().expect("proxy should be set");
+        assert_eq!(proxy.url(), "http://proxy.example.com:8080");
+        assert_eq!(proxy.username(), &Some("user".to_string()));
+        assert_eq!(proxy.password(), &Some("pass".to_string()));
+    }
+
+    #[test]
+    fn settings_with_timeouts_returns_given_timeouts() {
+        let settings = Settings::new()
+            .with_connect_timeout(Duration::from_secs(5))
+            .with_request_timeout(Duration::from_secs(30))
+            .with_slow_request_timeout(Duration::from_secs(300));
+
+        assert_eq!(settings.connect_timeout(), Some(Duration::from_secs(5)));
+        assert_eq!(settings.request_timeout(), Some(Duration::from_secs(30)));
+        assert_eq!(
+            settings.slow_request_timeout(),
+            Some(Duration::from_secs(300))
+        );
+    }
+
+    #[test]
+    fn settings_with_user_agent_returns_given_user_agent() {
+        let settings = Settings::new().with_user_agent("my-agent/1.0");
+
+        assert_eq!(settings.user_agent(), "my-agent/1.0");
+    }
+}